@@ -1,29 +1,82 @@
-// Basic hole-tracking array with compaction logic
+// Hole-tracking array with a free list, so reclaimed slots are reused without the O(n)
+// rewrite (and index invalidation) that `compact` requires.
 
 pub struct HoleArray<T> {
     data: Vec<Option<T>>,
+    free: Vec<usize>,
 }
 
 impl<T> HoleArray<T> {
     pub fn new() -> Self {
-        Self { data: Vec::new() }
+        Self {
+            data: Vec::new(),
+            free: Vec::new(),
+        }
     }
 
-    pub fn push(&mut self, value: T) {
-        self.data.push(Some(value));
+    /// Writes `value` into a free hole if one exists, otherwise appends. Returns the
+    /// slot index, which stays valid (and stable) until the slot is removed or
+    /// `compact` moves it.
+    pub fn push(&mut self, value: T) -> usize {
+        if let Some(index) = self.free.pop() {
+            self.data[index] = Some(value);
+            index
+        } else {
+            self.data.push(Some(value));
+            self.data.len() - 1
+        }
     }
 
-    pub fn mark_hole(&mut self, index: usize) {
-        if let Some(slot) = self.data.get_mut(index) {
-            *slot = None;
+    /// Same as `push`, named for callers that use `HoleArray` as a slab/arena and want
+    /// the stable handle emphasized.
+    pub fn insert(&mut self, value: T) -> usize {
+        self.push(value)
+    }
+
+    /// Takes the value out of `index` and records the slot as free for reuse.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let slot = self.data.get_mut(index)?;
+        let value = slot.take();
+        if value.is_some() {
+            self.free.push(index);
         }
+        value
+    }
+
+    pub fn mark_hole(&mut self, index: usize) {
+        self.remove(index);
+    }
+
+    pub fn live_count(&self) -> usize {
+        self.data.len() - self.free.len()
     }
 
-    pub fn compact(&mut self) {
-        self.data.retain(|x| x.is_some());
+    /// Rewrites the array with holes squeezed out. Returns `(old_index, new_index)` for
+    /// every value that moved, so callers holding external references can fix them up.
+    pub fn compact(&mut self) -> Vec<(usize, usize)> {
+        let mut remap = Vec::new();
+        let mut new_data = Vec::with_capacity(self.live_count());
+        for (old_index, slot) in self.data.drain(..).enumerate() {
+            if let Some(value) = slot {
+                let new_index = new_data.len();
+                if new_index != old_index {
+                    remap.push((old_index, new_index));
+                }
+                new_data.push(Some(value));
+            }
+        }
+        self.data = new_data;
+        self.free.clear();
+        remap
     }
 
     pub fn iter_valid(&self) -> impl Iterator<Item = &T> {
         self.data.iter().filter_map(|x| x.as_ref())
     }
 }
+
+impl<T> Default for HoleArray<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
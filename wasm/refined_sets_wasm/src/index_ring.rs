@@ -1,53 +1,101 @@
 use wasm_bindgen::prelude::*;
 
+const DEFAULT_BITS: usize = 24;
+
 #[wasm_bindgen]
 pub struct IndexRing {
     data: Vec<u8>,
-    head: usize, 
-    len: usize,  
+    head: usize,
+    len: usize,
+    num_bits: usize,
 }
 
 #[wasm_bindgen]
 impl IndexRing {
     #[wasm_bindgen(constructor)]
     pub fn new(slots: usize) -> IndexRing {
+        IndexRing::new_with_bits(slots, DEFAULT_BITS)
+    }
+
+    /// Same as `new`, but packs each slot into `num_bits` (1..=32) instead of the
+    /// default 24-bit layout, so callers whose values never exceed a known range
+    /// (e.g. 17-bit ids) can store them at close to the theoretical minimum size.
+    #[wasm_bindgen(js_name = newWithBits)]
+    pub fn new_with_bits(slots: usize, num_bits: usize) -> IndexRing {
+        assert!((1..=32).contains(&num_bits));
         IndexRing {
-            data: vec![0; slots * 3],
+            data: vec![0; Self::bytes_for(slots, num_bits)],
             head: 0,
             len: 0,
+            num_bits,
         }
     }
 
-    fn slot_to_byte(&self, slot_idx: usize) -> usize {
-        (slot_idx % self.capacity_in_slots()) * 3
+    // The packed minimum plus one spare byte, which `capacity_in_slots` leaves out.
+    // `load_word`/`store_word` clamp their 8-byte window to the end of `data`.
+    fn bytes_for(slots: usize, num_bits: usize) -> usize {
+        (slots * num_bits).div_ceil(8) + 1
+    }
+
+    fn slot_mask(&self) -> u64 {
+        if self.num_bits == 64 {
+            u64::MAX
+        } else {
+            (1u64 << self.num_bits) - 1
+        }
     }
 
     fn capacity_in_slots(&self) -> usize {
-        self.data.len() / 3
+        ((self.data.len() - 1) * 8) / self.num_bits
+    }
+
+    // Loads the 8-byte little-endian window starting at `byte_off`, zero-padding
+    // whatever part of the window lies past the end of `data`.
+    fn load_word(&self, byte_off: usize) -> u64 {
+        let mut window = [0u8; 8];
+        let avail = (self.data.len() - byte_off).min(8);
+        window[..avail].copy_from_slice(&self.data[byte_off..byte_off + avail]);
+        u64::from_le_bytes(window)
+    }
+
+    fn store_word(&mut self, byte_off: usize, word: u64) {
+        let avail = (self.data.len() - byte_off).min(8);
+        self.data[byte_off..byte_off + avail].copy_from_slice(&word.to_le_bytes()[..avail]);
+    }
+
+    fn get_bits(&self, slot_idx: usize) -> u32 {
+        let bit_off = slot_idx * self.num_bits;
+        let word = self.load_word(bit_off / 8);
+        ((word >> (bit_off % 8)) & self.slot_mask()) as u32
+    }
+
+    // Read-modify-write of the bits for `slot_idx`: load the straddling word into a
+    // mini_buffer, clear the slot's bits, OR in the new value at the right shift, and
+    // flush the touched bytes back to `data`.
+    fn set_bits(&mut self, slot_idx: usize, value: u32) {
+        let bit_off = slot_idx * self.num_bits;
+        let byte_off = bit_off / 8;
+        let shift = bit_off % 8;
+        let mut mini_buffer = self.load_word(byte_off);
+        mini_buffer &= !(self.slot_mask() << shift);
+        mini_buffer |= (u64::from(value) & self.slot_mask()) << shift;
+        self.store_word(byte_off, mini_buffer);
     }
 
     pub fn push(&mut self, value: u32) {
-        assert!(value <= 0xFFFFFF);
+        assert!(u64::from(value) <= self.slot_mask());
         if self.len == self.capacity_in_slots() {
             // needs resize (similar to above: grow by 2× slots)
             let new_slots = self.capacity_in_slots().max(1) * 2;
-            let mut new_data = vec![0; new_slots * 3];
-            // copy old contents into new_data in correct order
+            let mut new_ring = IndexRing::new_with_bits(new_slots, self.num_bits);
             for i in 0..self.len {
-                let v = self.get(i);
-                let off = i * 3;
-                new_data[off] = (v & 0xFF) as u8;
-                new_data[off + 1] = ((v >> 8) & 0xFF) as u8;
-                new_data[off + 2] = ((v >> 16) & 0xFF) as u8;
+                new_ring.push(self.get(i));
             }
-            self.data = new_data;
+            self.data = new_ring.data;
             self.head = 0;
         }
         let tail_slot = (self.head + self.len) % self.capacity_in_slots();
-        let byte_off = self.slot_to_byte(tail_slot);
-        self.data[byte_off] = (value & 0xFF) as u8;
-        self.data[byte_off + 1] = ((value >> 8) & 0xFF) as u8;
-        self.data[byte_off + 2] = ((value >> 16) & 0xFF) as u8;
+        self.set_bits(tail_slot, value);
         self.len += 1;
     }
 
@@ -55,10 +103,7 @@ impl IndexRing {
         if self.len == 0 {
             return None;
         }
-        let byte_off = self.slot_to_byte(self.head);
-        let val = u32::from(self.data[byte_off])
-            | (u32::from(self.data[byte_off + 1]) << 8)
-            | (u32::from(self.data[byte_off + 2]) << 16);
+        let val = self.get_bits(self.head);
         self.head = (self.head + 1) % self.capacity_in_slots();
         self.len -= 1;
         Some(val)
@@ -68,10 +113,7 @@ impl IndexRing {
         if self.len == 0 {
             return None;
         }
-        let byte_off = self.slot_to_byte(self.head);
-        let val = u32::from(self.data[byte_off])
-            | (u32::from(self.data[byte_off + 1]) << 8)
-            | (u32::from(self.data[byte_off + 2]) << 16);
+        let val = self.get_bits(self.head);
         self.head = (self.head + 1) % self.capacity_in_slots();
         self.len -= 1;
         Some(val)
@@ -80,16 +122,18 @@ impl IndexRing {
     pub fn get(&self, index: usize) -> u32 {
         assert!(index < self.len);
         let slot = (self.head + index) % self.capacity_in_slots();
-        let off = slot * 3;
-        u32::from(self.data[off])
-            | (u32::from(self.data[off + 1]) << 8)
-            | (u32::from(self.data[off + 2]) << 16)
+        self.get_bits(slot)
     }
 
     pub fn len(&self) -> usize {
         self.len
     }
 
+    #[wasm_bindgen(js_name = isEmpty)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     #[wasm_bindgen(js_name = toArray)]
     pub fn to_array(&self) -> Vec<u32> {
         let mut out = Vec::with_capacity(self.len);
@@ -101,16 +145,60 @@ impl IndexRing {
 
     #[wasm_bindgen(js_name = compact)]
     pub fn compact(&mut self) {
-        let new_slots = self.len;
-        let mut new_data = vec![0; new_slots * 3];
+        let mut new_ring = IndexRing::new_with_bits(self.len, self.num_bits);
         for i in 0..self.len {
-            let v = self.get(i);
-            let off = i * 3;
-            new_data[off] = (v & 0xFF) as u8;
-            new_data[off + 1] = ((v >> 8) & 0xFF) as u8;
-            new_data[off + 2] = ((v >> 16) & 0xFF) as u8;
+            new_ring.push(self.get(i));
         }
-        self.data = new_data;
+        self.data = new_ring.data;
         self.head = 0;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_and_get_round_trip_at_narrow_bit_width() {
+        let mut ring = IndexRing::new_with_bits(4, 5);
+        for &v in &[0, 1, 17, 31] {
+            ring.push(v);
+        }
+        assert_eq!(ring.to_array(), vec![0, 1, 17, 31]);
+    }
+
+    #[test]
+    fn push_and_get_round_trip_at_full_32_bit_width() {
+        let mut ring = IndexRing::new_with_bits(3, 32);
+        for &v in &[0, u32::MAX, 123_456_789] {
+            ring.push(v);
+        }
+        assert_eq!(ring.to_array(), vec![0, u32::MAX, 123_456_789]);
+    }
+
+    #[test]
+    fn shift_and_push_wrap_around_non_default_width() {
+        let mut ring = IndexRing::new_with_bits(2, 9);
+        ring.push(100);
+        ring.push(200);
+        assert_eq!(ring.shift(), Some(100));
+        ring.push(300);
+        assert_eq!(ring.to_array(), vec![200, 300]);
+    }
+
+    #[test]
+    fn push_past_capacity_grows_and_preserves_order() {
+        let mut ring = IndexRing::new_with_bits(1, 10);
+        for v in 0..20u32 {
+            ring.push(v);
+        }
+        assert_eq!(ring.to_array(), (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_above_bit_width_panics() {
+        let mut ring = IndexRing::new_with_bits(1, 4);
+        ring.push(16);
+    }
+}
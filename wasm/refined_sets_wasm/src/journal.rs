@@ -0,0 +1,297 @@
+//! Append-only operation log for `IndexRing`/`HoleArray`, so their state survives a
+//! reload across the WASM boundary (e.g. persisted to IndexedDB or a file).
+//!
+//! Each record is framed as `[u32 length][payload][u32 crc32]`. Replay scans records
+//! sequentially and stops at the first record whose CRC fails or whose length runs
+//! past the end of the buffer, treating that point as the truncation boundary rather
+//! than erroring — a log that was only partially flushed before a crash is still
+//! readable up to its last complete record.
+
+use wasm_bindgen::prelude::*;
+
+use crate::hole_array::HoleArray;
+use crate::index_ring::IndexRing;
+
+/// A single logged mutation. `Snapshot` is what `checkpoint` writes in place of a long
+/// op history.
+#[derive(Debug, PartialEq, Eq)]
+enum Op {
+    Push(u32),
+    Shift,
+    MarkHole(usize),
+    Snapshot(Vec<u32>),
+}
+
+const TAG_PUSH: u8 = 0;
+const TAG_SHIFT: u8 = 1;
+const TAG_MARK_HOLE: u8 = 2;
+const TAG_SNAPSHOT: u8 = 3;
+
+fn encode_op(op: &Op) -> Vec<u8> {
+    let mut out = Vec::new();
+    match op {
+        Op::Push(value) => {
+            out.push(TAG_PUSH);
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        Op::Shift => {
+            out.push(TAG_SHIFT);
+        }
+        Op::MarkHole(index) => {
+            out.push(TAG_MARK_HOLE);
+            out.extend_from_slice(&(*index as u64).to_le_bytes());
+        }
+        Op::Snapshot(values) => {
+            out.push(TAG_SNAPSHOT);
+            out.extend_from_slice(&(values.len() as u32).to_le_bytes());
+            for value in values {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+    }
+    out
+}
+
+fn decode_op(payload: &[u8]) -> Option<Op> {
+    match *payload.first()? {
+        TAG_PUSH => {
+            let bytes = payload.get(1..5)?;
+            Some(Op::Push(u32::from_le_bytes(bytes.try_into().ok()?)))
+        }
+        TAG_SHIFT => Some(Op::Shift),
+        TAG_MARK_HOLE => {
+            let bytes = payload.get(1..9)?;
+            Some(Op::MarkHole(u64::from_le_bytes(bytes.try_into().ok()?) as usize))
+        }
+        TAG_SNAPSHOT => {
+            let count = u32::from_le_bytes(payload.get(1..5)?.try_into().ok()?) as usize;
+            let mut values = Vec::with_capacity(count);
+            let mut pos = 5;
+            for _ in 0..count {
+                let bytes = payload.get(pos..pos + 4)?;
+                values.push(u32::from_le_bytes(bytes.try_into().ok()?));
+                pos += 4;
+            }
+            Some(Op::Snapshot(values))
+        }
+        _ => None,
+    }
+}
+
+/// Scans `bytes` as a sequence of length-prefixed, CRC-checked records, stopping at the
+/// first one that's corrupt or truncated. Returns the verified prefix of `bytes` and
+/// the ops decoded from it.
+fn decode_records(bytes: &[u8]) -> (&[u8], Vec<Op>) {
+    let mut ops = Vec::new();
+    let mut pos = 0;
+    while pos + 4 <= bytes.len() {
+        let len = u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap()) as usize;
+        let payload_start = pos + 4;
+        let remaining = bytes.len() - payload_start;
+        // `len` comes straight from the frame and may be corrupted (up to `u32::MAX`);
+        // check it against what's actually left with a checked add so a bogus length
+        // can't wrap `usize` on this crate's wasm32 target (32-bit, same range as the
+        // `u32` it was read from) and turn into an out-of-order slice panic.
+        let fits = len.checked_add(4).is_some_and(|needed| needed <= remaining);
+        if !fits {
+            break;
+        }
+        let payload_end = payload_start + len;
+        let crc_end = payload_end + 4;
+        let payload = &bytes[payload_start..payload_end];
+        let expected_crc = u32::from_le_bytes(bytes[payload_end..crc_end].try_into().unwrap());
+        if crc32(payload) != expected_crc {
+            break;
+        }
+        match decode_op(payload) {
+            Some(op) => ops.push(op),
+            None => break,
+        }
+        pos = crc_end;
+    }
+    (&bytes[..pos], ops)
+}
+
+/// An append-only log of ops, framed with length + CRC32 for crash-consistent
+/// persistence, with replay helpers that rebuild an `IndexRing` or `HoleArray` from it.
+#[wasm_bindgen]
+pub struct Journal {
+    records: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl Journal {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self { records: Vec::new() }
+    }
+
+    fn append(&mut self, op: &Op) {
+        let payload = encode_op(op);
+        self.records.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        self.records.extend_from_slice(&payload);
+        self.records.extend_from_slice(&crc32(&payload).to_le_bytes());
+    }
+
+    #[wasm_bindgen(js_name = recordPush)]
+    pub fn record_push(&mut self, value: u32) {
+        self.append(&Op::Push(value));
+    }
+
+    #[wasm_bindgen(js_name = recordShift)]
+    pub fn record_shift(&mut self) {
+        self.append(&Op::Shift);
+    }
+
+    #[wasm_bindgen(js_name = recordMarkHole)]
+    pub fn record_mark_hole(&mut self, index: usize) {
+        self.append(&Op::MarkHole(index));
+    }
+
+    #[wasm_bindgen(js_name = toJournal)]
+    pub fn to_journal(&self) -> Vec<u8> {
+        self.records.clone()
+    }
+
+    /// Verifies `bytes` record-by-record and keeps only the valid prefix — see the
+    /// module docs for how truncation/corruption is handled.
+    #[wasm_bindgen(js_name = fromJournal)]
+    pub fn from_journal(bytes: &[u8]) -> Journal {
+        let (valid_prefix, _ops) = decode_records(bytes);
+        Journal {
+            records: valid_prefix.to_vec(),
+        }
+    }
+
+    /// Rewrites the log as a single compacted snapshot record, so a long-running
+    /// session's op history doesn't grow without bound.
+    pub fn checkpoint(&mut self, values: Vec<u32>) {
+        self.records.clear();
+        self.append(&Op::Snapshot(values));
+    }
+
+    /// Replays this journal's ops into `ring`, rebuilding the state it had when the
+    /// journal was written (`Shift`/`Push`/`Snapshot`; `MarkHole` doesn't apply to a
+    /// ring and is ignored).
+    #[wasm_bindgen(js_name = replayIntoIndexRing)]
+    pub fn replay_into_index_ring(&self, ring: &mut IndexRing) {
+        let (_, ops) = decode_records(&self.records);
+        for op in ops {
+            match op {
+                Op::Push(value) => ring.push(value),
+                Op::Shift => {
+                    ring.shift();
+                }
+                Op::Snapshot(values) => values.into_iter().for_each(|v| ring.push(v)),
+                Op::MarkHole(_) => {}
+            }
+        }
+    }
+}
+
+impl Journal {
+    /// Replays this journal's ops into `arr` (`Push`/`MarkHole`/`Snapshot`; `Shift`
+    /// doesn't apply to a `HoleArray` and is ignored). Not wasm-exposed since
+    /// `HoleArray<T>` is a plain Rust slab type, not a `wasm_bindgen` struct.
+    pub fn replay_into_hole_array(&self, arr: &mut HoleArray<u32>) {
+        let (_, ops) = decode_records(&self.records);
+        for op in ops {
+            match op {
+                Op::Push(value) => {
+                    arr.push(value);
+                }
+                Op::MarkHole(index) => arr.mark_hole(index),
+                Op::Snapshot(values) => values.into_iter().for_each(|v| {
+                    arr.push(v);
+                }),
+                Op::Shift => {}
+            }
+        }
+    }
+}
+
+impl Default for Journal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Standard IEEE 802.3 CRC-32 (poly 0xEDB88320), computed table-free since this crate
+// has no other use for a lookup table.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFFFFFFu32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = 0u32.wrapping_sub(crc & 1);
+            crc = (crc >> 1) ^ (0xEDB88320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_into_index_ring_rebuilds_push_and_shift_history() {
+        let mut journal = Journal::new();
+        journal.record_push(1);
+        journal.record_push(2);
+        journal.record_shift();
+        journal.record_push(3);
+
+        let mut ring = IndexRing::new_with_bits(4, 8);
+        journal.replay_into_index_ring(&mut ring);
+        assert_eq!(ring.to_array(), vec![2, 3]);
+    }
+
+    #[test]
+    fn replay_into_hole_array_applies_push_and_mark_hole() {
+        let mut journal = Journal::new();
+        journal.record_push(10);
+        journal.record_push(20);
+        journal.record_mark_hole(0);
+
+        let mut arr = HoleArray::<u32>::new();
+        journal.replay_into_hole_array(&mut arr);
+        assert_eq!(arr.iter_valid().copied().collect::<Vec<_>>(), vec![20]);
+    }
+
+    #[test]
+    fn from_journal_round_trips_through_to_journal() {
+        let mut journal = Journal::new();
+        journal.record_push(7);
+        journal.record_shift();
+        journal.record_mark_hole(2);
+
+        let bytes = journal.to_journal();
+        let reloaded = Journal::from_journal(&bytes);
+        assert_eq!(reloaded.to_journal(), bytes);
+    }
+
+    #[test]
+    fn from_journal_truncates_at_corrupted_trailing_record() {
+        let mut journal = Journal::new();
+        journal.record_push(1);
+        journal.record_push(2);
+
+        let mut bytes = journal.to_journal();
+        bytes.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]); // bogus trailing length prefix
+        let reloaded = Journal::from_journal(&bytes);
+        assert_eq!(reloaded.to_journal(), journal.to_journal());
+    }
+
+    #[test]
+    fn checkpoint_replays_as_a_single_snapshot() {
+        let mut journal = Journal::new();
+        journal.record_push(1);
+        journal.record_push(2);
+        journal.checkpoint(vec![9, 8, 7]);
+
+        let mut ring = IndexRing::new_with_bits(4, 8);
+        journal.replay_into_index_ring(&mut ring);
+        assert_eq!(ring.to_array(), vec![9, 8, 7]);
+    }
+}
@@ -2,6 +2,10 @@ use wasm_bindgen::prelude::*;
 
 pub mod packed_index;
 pub mod hole_array;
+pub mod index_ring;
+pub mod journal;
+pub mod roaring_set;
+pub mod set_ops;
 pub mod utils;
 
 #[wasm_bindgen]
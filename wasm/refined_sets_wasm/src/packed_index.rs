@@ -0,0 +1,312 @@
+//! Block-oriented codec for compressing sorted `u32` runs, used when exporting or
+//! importing large index sets across the WASM boundary.
+
+use wasm_bindgen::prelude::*;
+
+const BLOCK_SIZE: usize = 128;
+
+/// Compresses a sorted slice of `u32` values into fixed-size delta-encoded blocks.
+///
+/// Each full block of `BLOCK_SIZE` values is encoded as a one-byte bit-width header
+/// followed by the deltas from the previous value, bit-packed at that width. The
+/// trailing values that don't fill a whole block are written the same way, just with
+/// fewer than `BLOCK_SIZE` deltas.
+#[wasm_bindgen(js_name = packBlocks)]
+pub fn pack_blocks(values: &[u32]) -> Vec<u8> {
+    // A leading value count lets the decoder know how many deltas the trailing
+    // remainder block holds, since its own header only carries the bit width.
+    let mut out = Vec::with_capacity(4 + values.len());
+    out.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    let mut prev = 0u32;
+    for block in values.chunks(BLOCK_SIZE) {
+        let deltas: Vec<u32> = block
+            .iter()
+            .map(|&v| {
+                assert!(v >= prev, "pack_blocks requires a sorted, non-decreasing input");
+                let delta = v - prev;
+                prev = v;
+                delta
+            })
+            .collect();
+        let num_bits = deltas.iter().map(|&d| bits_needed(d)).max().unwrap_or(0);
+        out.push(num_bits);
+        pack_deltas(&deltas, num_bits, &mut out);
+    }
+    out
+}
+
+/// Reverses [`pack_blocks`]: reads each block's width header, unpacks its deltas, and
+/// prefix-sums them back into the original values.
+#[wasm_bindgen(js_name = unpackBlocks)]
+pub fn unpack_blocks(data: &[u8]) -> Vec<u32> {
+    if data.len() < 4 {
+        return Vec::new();
+    }
+    let total = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    let mut out = Vec::with_capacity(total);
+    let mut prev = 0u32;
+    let mut pos = 4;
+    while out.len() < total {
+        let num_bits = data[pos];
+        pos += 1;
+        let block_len = (total - out.len()).min(BLOCK_SIZE);
+        let (deltas, consumed) = unpack_deltas(&data[pos..], num_bits, block_len);
+        pos += consumed;
+        for delta in deltas {
+            prev += delta;
+            out.push(prev);
+        }
+    }
+    out
+}
+
+fn bits_needed(value: u32) -> u8 {
+    32 - value.leading_zeros() as u8
+}
+
+/// A sorted, bit-packed `u32` set (same per-slot layout as `IndexRing`, minus the ring
+/// wraparound) that answers range queries by binary-searching its packed slots instead
+/// of materializing the whole set.
+#[wasm_bindgen]
+pub struct PackedSortedSet {
+    data: Vec<u8>,
+    len: usize,
+    num_bits: usize,
+}
+
+#[wasm_bindgen]
+impl PackedSortedSet {
+    #[wasm_bindgen(constructor)]
+    pub fn new(num_bits: usize) -> PackedSortedSet {
+        assert!((1..=32).contains(&num_bits));
+        PackedSortedSet {
+            data: Vec::new(),
+            len: 0,
+            num_bits,
+        }
+    }
+
+    /// Appends `value`, which must be `>=` the last pushed value (the backing storage
+    /// stays sorted so `range`/`count_in_range` can binary-search it).
+    pub fn push(&mut self, value: u32) {
+        assert!(self.len == 0 || value >= self.get(self.len - 1));
+        let bit_off = self.len * self.num_bits;
+        // +1 spare byte for the 8-byte straddling load in `load_word`.
+        let needed_bytes = (bit_off + self.num_bits).div_ceil(8) + 1;
+        if needed_bytes > self.data.len() {
+            self.data.resize(needed_bytes, 0);
+        }
+        self.set_bits(self.len, value);
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[wasm_bindgen(js_name = isEmpty)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn load_word(&self, byte_off: usize) -> u64 {
+        let mut window = [0u8; 8];
+        let avail = (self.data.len() - byte_off).min(8);
+        window[..avail].copy_from_slice(&self.data[byte_off..byte_off + avail]);
+        u64::from_le_bytes(window)
+    }
+
+    fn set_bits(&mut self, slot_idx: usize, value: u32) {
+        let bit_off = slot_idx * self.num_bits;
+        let byte_off = bit_off / 8;
+        let shift = bit_off % 8;
+        let slot_mask = mask(self.num_bits);
+        let mut word = self.load_word(byte_off);
+        word &= !(slot_mask << shift);
+        word |= (u64::from(value) & slot_mask) << shift;
+        let avail = (self.data.len() - byte_off).min(8);
+        self.data[byte_off..byte_off + avail].copy_from_slice(&word.to_le_bytes()[..avail]);
+    }
+
+    pub fn get(&self, index: usize) -> u32 {
+        assert!(index < self.len);
+        let bit_off = index * self.num_bits;
+        let word = self.load_word(bit_off / 8);
+        ((word >> (bit_off % 8)) & mask(self.num_bits)) as u32
+    }
+
+    // First index `i` with `get(i) >= target`.
+    fn lower_bound(&self, target: u32) -> usize {
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.get(mid) < target {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    // First index `i` with `get(i) > target`.
+    fn upper_bound(&self, target: u32) -> usize {
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.get(mid) <= target {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    /// Returns the contiguous slice of members in `[low, high]`. Outside that range only
+    /// the slots probed by the two binary searches are decoded.
+    pub fn range(&self, low: u32, high: u32) -> Vec<u32> {
+        if low > high {
+            return Vec::new();
+        }
+        let start = self.lower_bound(low);
+        let end = self.upper_bound(high);
+        (start..end).map(|i| self.get(i)).collect()
+    }
+
+    #[wasm_bindgen(js_name = countInRange)]
+    pub fn count_in_range(&self, low: u32, high: u32) -> usize {
+        if low > high {
+            return 0;
+        }
+        self.upper_bound(high) - self.lower_bound(low)
+    }
+}
+
+// Tight inner loop the compiler can auto-vectorize on its own; no hand-written SIMD
+// path, since a placebo one is worse than none and a genuine one isn't worth the
+// unsafe surface for this codec's block sizes.
+fn pack_deltas(deltas: &[u32], num_bits: u8, out: &mut Vec<u8>) {
+    let num_bits = num_bits as usize;
+    let mut mini_buffer: u64 = 0;
+    let mut mini_buffer_len: usize = 0;
+    for &delta in deltas {
+        mini_buffer |= (u64::from(delta) & mask(num_bits)) << mini_buffer_len;
+        mini_buffer_len += num_bits;
+        while mini_buffer_len >= 8 {
+            out.push((mini_buffer & 0xFF) as u8);
+            mini_buffer >>= 8;
+            mini_buffer_len -= 8;
+        }
+    }
+    if mini_buffer_len > 0 {
+        out.push((mini_buffer & 0xFF) as u8);
+    }
+}
+
+fn unpack_deltas(data: &[u8], num_bits: u8, count: usize) -> (Vec<u32>, usize) {
+    let num_bits = num_bits as usize;
+    let mut deltas = Vec::with_capacity(count);
+    if num_bits == 0 {
+        deltas.resize(count, 0);
+        return (deltas, 0);
+    }
+    let mut mini_buffer: u64 = 0;
+    let mut mini_buffer_len: usize = 0;
+    let mut byte_pos = 0;
+    while deltas.len() < count {
+        while mini_buffer_len < num_bits {
+            mini_buffer |= u64::from(data[byte_pos]) << mini_buffer_len;
+            mini_buffer_len += 8;
+            byte_pos += 1;
+        }
+        deltas.push((mini_buffer & mask(num_bits)) as u32);
+        mini_buffer >>= num_bits;
+        mini_buffer_len -= num_bits;
+    }
+    let consumed_bits = count * num_bits;
+    let consumed_bytes = consumed_bits.div_ceil(8);
+    (deltas, consumed_bytes)
+}
+
+fn mask(num_bits: usize) -> u64 {
+    if num_bits == 0 {
+        0
+    } else if num_bits >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << num_bits) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_blocks_round_trip_empty() {
+        assert_eq!(unpack_blocks(&pack_blocks(&[])), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn pack_blocks_round_trip_single_block() {
+        let values: Vec<u32> = vec![0, 1, 1, 5, 8, 8, 1000];
+        assert_eq!(unpack_blocks(&pack_blocks(&values)), values);
+    }
+
+    #[test]
+    fn pack_blocks_round_trip_multiple_blocks() {
+        // Spans several `BLOCK_SIZE` blocks plus a partial trailing block, with deltas
+        // that vary enough to exercise different per-block bit widths.
+        let values: Vec<u32> = (0..(BLOCK_SIZE * 3 + 17) as u32)
+            .map(|i| i * i % 5000)
+            .scan(0u32, |running, step| {
+                *running += step;
+                Some(*running)
+            })
+            .collect();
+        assert_eq!(unpack_blocks(&pack_blocks(&values)), values);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-decreasing")]
+    fn pack_blocks_rejects_unsorted_input() {
+        pack_blocks(&[5, 3]);
+    }
+
+    fn brute_force_range(values: &[u32], low: u32, high: u32) -> Vec<u32> {
+        values
+            .iter()
+            .copied()
+            .filter(|&v| v >= low && v <= high)
+            .collect()
+    }
+
+    #[test]
+    fn packed_sorted_set_range_matches_brute_force() {
+        let values: Vec<u32> = vec![2, 4, 4, 7, 11, 11, 11, 30, 65, 65, 200];
+        let mut set = PackedSortedSet::new(32);
+        for &v in &values {
+            set.push(v);
+        }
+        for (low, high) in [(0, 0), (0, 300), (4, 11), (5, 10), (11, 11), (66, 199), (200, 200)] {
+            assert_eq!(
+                set.range(low, high),
+                brute_force_range(&values, low, high),
+                "range({low}, {high})"
+            );
+            assert_eq!(
+                set.count_in_range(low, high),
+                brute_force_range(&values, low, high).len()
+            );
+        }
+    }
+
+    #[test]
+    fn packed_sorted_set_range_empty_when_low_exceeds_high() {
+        let mut set = PackedSortedSet::new(8);
+        set.push(3);
+        assert_eq!(set.range(10, 1), Vec::<u32>::new());
+        assert_eq!(set.count_in_range(10, 1), 0);
+    }
+}
@@ -0,0 +1,285 @@
+use wasm_bindgen::prelude::*;
+
+// Cardinality at which a container switches its representation. Below this, a sorted
+// array of u16s is smaller than a 8 KiB bitmap; above it, the bitmap wins.
+const ARRAY_MAX_CARDINALITY: usize = 4096;
+const BITMAP_WORDS: usize = 1024; // 1024 * 64 bits = 65536 = one full u16 key space
+
+enum Container {
+    Array(Vec<u16>),
+    Bitmap(Box<[u64; BITMAP_WORDS]>),
+}
+
+impl Container {
+    fn new() -> Container {
+        Container::Array(Vec::new())
+    }
+
+    fn cardinality(&self) -> usize {
+        match self {
+            Container::Array(values) => values.len(),
+            Container::Bitmap(words) => words.iter().map(|w| w.count_ones() as usize).sum(),
+        }
+    }
+
+    fn contains(&self, low: u16) -> bool {
+        match self {
+            Container::Array(values) => values.binary_search(&low).is_ok(),
+            Container::Bitmap(words) => {
+                let word_idx = (low / 64) as usize;
+                (words[word_idx] >> (low % 64)) & 1 != 0
+            }
+        }
+    }
+
+    fn insert(&mut self, low: u16) {
+        match self {
+            Container::Array(values) => {
+                if let Err(at) = values.binary_search(&low) {
+                    values.insert(at, low);
+                }
+                if values.len() > ARRAY_MAX_CARDINALITY {
+                    self.convert_to_bitmap();
+                }
+            }
+            Container::Bitmap(words) => {
+                let word_idx = (low / 64) as usize;
+                words[word_idx] |= 1u64 << (low % 64);
+            }
+        }
+    }
+
+    fn convert_to_bitmap(&mut self) {
+        if let Container::Array(values) = self {
+            let mut words = Box::new([0u64; BITMAP_WORDS]);
+            for &low in values.iter() {
+                words[(low / 64) as usize] |= 1u64 << (low % 64);
+            }
+            *self = Container::Bitmap(words);
+        }
+    }
+
+    fn convert_to_array(&mut self) {
+        let card = self.cardinality();
+        if let Container::Bitmap(words) = self {
+            let mut values = Vec::with_capacity(card);
+            for (word_idx, &word) in words.iter().enumerate() {
+                let mut bits = word;
+                while bits != 0 {
+                    let bit = bits.trailing_zeros();
+                    values.push((word_idx * 64 + bit as usize) as u16);
+                    bits &= bits - 1;
+                }
+            }
+            *self = Container::Array(values);
+        }
+    }
+
+    /// Picks the result representation by the same cardinality threshold used for
+    /// inserts, rather than always producing a bitmap or always an array.
+    fn union(&self, other: &Container) -> Container {
+        let mut result = match self {
+            Container::Bitmap(words) => Container::Bitmap(words.clone()),
+            Container::Array(_) => Container::Array(Vec::new()),
+        };
+        if let Container::Array(_) = result {
+            if let Container::Array(values) = self {
+                for &low in values {
+                    result.insert(low);
+                }
+            }
+        }
+        match other {
+            Container::Array(values) => {
+                for &low in values {
+                    result.insert(low);
+                }
+            }
+            Container::Bitmap(words) => {
+                result.convert_to_bitmap();
+                if let Container::Bitmap(result_words) = &mut result {
+                    for (i, &word) in words.iter().enumerate() {
+                        result_words[i] |= word;
+                    }
+                }
+            }
+        }
+        if result.cardinality() <= ARRAY_MAX_CARDINALITY {
+            result.convert_to_array();
+        }
+        result
+    }
+
+    fn intersection(&self, other: &Container) -> Container {
+        let mut out = Container::new();
+        let (smaller, larger) = match self.cardinality() <= other.cardinality() {
+            true => (self, other),
+            false => (other, self),
+        };
+        match smaller {
+            Container::Array(values) => {
+                for &low in values {
+                    if larger.contains(low) {
+                        out.insert(low);
+                    }
+                }
+            }
+            Container::Bitmap(words) => {
+                for (word_idx, &word) in words.iter().enumerate() {
+                    let mut bits = word;
+                    while bits != 0 {
+                        let bit = bits.trailing_zeros();
+                        let low = (word_idx * 64 + bit as usize) as u16;
+                        if larger.contains(low) {
+                            out.insert(low);
+                        }
+                        bits &= bits - 1;
+                    }
+                }
+            }
+        }
+        if out.cardinality() > ARRAY_MAX_CARDINALITY {
+            out.convert_to_bitmap();
+        }
+        out
+    }
+}
+
+/// A hybrid sparse/dense set of `u32` ids, partitioned by the high 16 bits into
+/// per-block containers (array or bitmap) that each pick their own representation by
+/// cardinality. Memory scales with the data actually stored rather than the largest id.
+#[wasm_bindgen]
+pub struct RoaringSet {
+    // Sorted by key so lookups binary-search and merges can walk both sides in order.
+    containers: Vec<(u16, Container)>,
+}
+
+#[wasm_bindgen]
+impl RoaringSet {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> RoaringSet {
+        RoaringSet {
+            containers: Vec::new(),
+        }
+    }
+
+    fn split(value: u32) -> (u16, u16) {
+        ((value >> 16) as u16, value as u16)
+    }
+
+    fn container_index(&self, key: u16) -> Result<usize, usize> {
+        self.containers.binary_search_by_key(&key, |(k, _)| *k)
+    }
+
+    pub fn insert(&mut self, value: u32) {
+        let (key, low) = Self::split(value);
+        let idx = match self.container_index(key) {
+            Ok(idx) => idx,
+            Err(at) => {
+                self.containers.insert(at, (key, Container::new()));
+                at
+            }
+        };
+        self.containers[idx].1.insert(low);
+    }
+
+    pub fn contains(&self, value: u32) -> bool {
+        let (key, low) = Self::split(value);
+        match self.container_index(key) {
+            Ok(idx) => self.containers[idx].1.contains(low),
+            Err(_) => false,
+        }
+    }
+
+    pub fn cardinality(&self) -> usize {
+        self.containers.iter().map(|(_, c)| c.cardinality()).sum()
+    }
+
+    pub fn union(&self, other: &RoaringSet) -> RoaringSet {
+        let mut result = RoaringSet::new();
+        let mut i = 0;
+        let mut j = 0;
+        while i < self.containers.len() || j < other.containers.len() {
+            let a = self.containers.get(i);
+            let b = other.containers.get(j);
+            match (a, b) {
+                (Some((ka, ca)), Some((kb, cb))) if ka == kb => {
+                    result.containers.push((*ka, ca.union(cb)));
+                    i += 1;
+                    j += 1;
+                }
+                (Some((ka, ca)), Some((kb, _))) if ka < kb => {
+                    result.containers.push((*ka, ca.union(&Container::new())));
+                    i += 1;
+                }
+                (Some(_), Some((kb, cb))) => {
+                    result.containers.push((*kb, cb.union(&Container::new())));
+                    j += 1;
+                }
+                (Some((ka, ca)), None) => {
+                    result.containers.push((*ka, ca.union(&Container::new())));
+                    i += 1;
+                }
+                (None, Some((kb, cb))) => {
+                    result.containers.push((*kb, cb.union(&Container::new())));
+                    j += 1;
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &RoaringSet) -> RoaringSet {
+        let mut result = RoaringSet::new();
+        let mut i = 0;
+        let mut j = 0;
+        while i < self.containers.len() && j < other.containers.len() {
+            let (ka, ca) = &self.containers[i];
+            let (kb, cb) = &other.containers[j];
+            if ka == kb {
+                let merged = ca.intersection(cb);
+                if merged.cardinality() > 0 {
+                    result.containers.push((*ka, merged));
+                }
+                i += 1;
+                j += 1;
+            } else if ka < kb {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        result
+    }
+
+    #[wasm_bindgen(js_name = toArray)]
+    pub fn to_array(&self) -> Vec<u32> {
+        let mut out = Vec::with_capacity(self.cardinality());
+        for (key, container) in &self.containers {
+            match container {
+                Container::Array(values) => {
+                    out.extend(values.iter().map(|&low| ((*key as u32) << 16) | low as u32))
+                }
+                Container::Bitmap(words) => {
+                    for (word_idx, &word) in words.iter().enumerate() {
+                        let mut bits = word;
+                        while bits != 0 {
+                            let bit = bits.trailing_zeros();
+                            let low = (word_idx * 64 + bit as usize) as u32;
+                            out.push(((*key as u32) << 16) | low);
+                            bits &= bits - 1;
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Default for RoaringSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
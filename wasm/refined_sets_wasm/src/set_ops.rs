@@ -0,0 +1,307 @@
+use wasm_bindgen::prelude::*;
+
+use crate::index_ring::IndexRing;
+
+// How many 64-bit words a single summary word covers. Each summary bit stands for one
+// word and is set whenever that word is non-empty, so `iter()` can skip a whole block
+// whose summary word is zero and visit only the non-empty words of the others.
+const WORDS_PER_BLOCK: usize = 64;
+
+/// A dense bitset over `u32` ids, with word-at-a-time set algebra and a tiered summary
+/// index for fast iteration over sparse sets.
+#[wasm_bindgen]
+pub struct Set {
+    words: Vec<u64>,
+    summary: Vec<u64>,
+}
+
+#[wasm_bindgen]
+impl Set {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Set {
+        Set {
+            words: Vec::new(),
+            summary: Vec::new(),
+        }
+    }
+
+    fn block_count(word_count: usize) -> usize {
+        word_count.div_ceil(WORDS_PER_BLOCK)
+    }
+
+    fn ensure_word(&mut self, word_idx: usize) {
+        if word_idx >= self.words.len() {
+            self.words.resize(word_idx + 1, 0);
+            self.summary.resize(Self::block_count(self.words.len()), 0);
+        }
+    }
+
+    fn mark_summary(&mut self, word_idx: usize) {
+        let block = word_idx / WORDS_PER_BLOCK;
+        let bit = word_idx % WORDS_PER_BLOCK;
+        if self.words[word_idx] != 0 {
+            self.summary[block] |= 1u64 << bit;
+        } else {
+            self.summary[block] &= !(1u64 << bit);
+        }
+    }
+
+    pub fn insert(&mut self, value: u32) {
+        let word_idx = (value / 64) as usize;
+        let bit = value % 64;
+        self.ensure_word(word_idx);
+        self.words[word_idx] |= 1u64 << bit;
+        self.mark_summary(word_idx);
+    }
+
+    pub fn remove(&mut self, value: u32) {
+        let word_idx = (value / 64) as usize;
+        if word_idx >= self.words.len() {
+            return;
+        }
+        self.words[word_idx] &= !(1u64 << (value % 64));
+        self.mark_summary(word_idx);
+    }
+
+    pub fn contains(&self, value: u32) -> bool {
+        let word_idx = (value / 64) as usize;
+        match self.words.get(word_idx) {
+            Some(word) => (word >> (value % 64)) & 1 != 0,
+            None => false,
+        }
+    }
+
+    // Builds a result `Set` by combining `self` and `other` word-by-word with `op`,
+    // over the union of their word ranges (missing words on either side read as 0).
+    fn combine(&self, other: &Set, op: impl Fn(u64, u64) -> u64) -> Set {
+        let len = self.words.len().max(other.words.len());
+        let mut words = Vec::with_capacity(len);
+        for i in 0..len {
+            let a = self.words.get(i).copied().unwrap_or(0);
+            let b = other.words.get(i).copied().unwrap_or(0);
+            words.push(op(a, b));
+        }
+        let mut summary = vec![0u64; Self::block_count(words.len())];
+        for (i, &word) in words.iter().enumerate() {
+            if word != 0 {
+                summary[i / WORDS_PER_BLOCK] |= 1u64 << (i % WORDS_PER_BLOCK);
+            }
+        }
+        Set { words, summary }
+    }
+
+    pub fn union(&self, other: &Set) -> Set {
+        self.combine(other, |a, b| a | b)
+    }
+
+    pub fn intersection(&self, other: &Set) -> Set {
+        self.combine(other, |a, b| a & b)
+    }
+
+    pub fn difference(&self, other: &Set) -> Set {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    #[wasm_bindgen(js_name = symmetricDifference)]
+    pub fn symmetric_difference(&self, other: &Set) -> Set {
+        self.combine(other, |a, b| a ^ b)
+    }
+
+    #[wasm_bindgen(js_name = toArray)]
+    pub fn to_array(&self) -> Vec<u32> {
+        self.iter().collect()
+    }
+
+    #[wasm_bindgen(js_name = fromIndexRing)]
+    pub fn from_index_ring(ring: &IndexRing) -> Set {
+        let mut set = Set::new();
+        for value in ring.to_array() {
+            set.insert(value);
+        }
+        set
+    }
+
+    #[wasm_bindgen(js_name = toIndexRing)]
+    pub fn to_index_ring(&self) -> IndexRing {
+        // `Set` has no upper bound below `u32::MAX`, so the fixed 24-bit default layout
+        // isn't safe here; size the ring's bit width to the largest member actually
+        // stored (1 bit minimum, since `new_with_bits` requires at least that).
+        let max_value = self.iter().max().unwrap_or(0);
+        let num_bits = (32 - max_value.leading_zeros()).max(1) as usize;
+        let mut ring = IndexRing::new_with_bits(0, num_bits);
+        for value in self.iter() {
+            ring.push(value);
+        }
+        ring
+    }
+
+    /// Returns the members in `[low, high]` without materializing the whole set:
+    /// boundary words are AND-masked to the partial range, interior words are taken
+    /// whole, and only words inside the window are ever touched.
+    pub fn range(&self, low: u32, high: u32) -> Vec<u32> {
+        self.range_words(low, high)
+            .flat_map(|(word_idx, word)| {
+                SetBitIter(word).map(move |bit| (word_idx as u32) * 64 + bit)
+            })
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = countInRange)]
+    pub fn count_in_range(&self, low: u32, high: u32) -> usize {
+        self.range_words(low, high)
+            .map(|(_, word)| word.count_ones() as usize)
+            .sum()
+    }
+}
+
+impl Set {
+    // Yields `(word_idx, masked_word)` for every word overlapping `[low, high]`, with
+    // the first and last words AND-masked down to their partial-range bits.
+    fn range_words(&self, low: u32, high: u32) -> impl Iterator<Item = (usize, u64)> + '_ {
+        // The *unclamped* high word: when `high` falls past the set's populated span,
+        // `end_word` below gets clamped to the last real word, which is taken whole
+        // (not masked) since it isn't actually the window's upper boundary.
+        let high_word = (high / 64) as usize;
+        let (start_word, end_word) = if low > high || self.words.is_empty() {
+            (1, 0) // empty range: start > end so the loop below yields nothing
+        } else {
+            let start = (low / 64) as usize;
+            let end = high_word.min(self.words.len() - 1);
+            (start, end)
+        };
+        let start_bit = (low % 64) as usize;
+        let end_bit = (high % 64) as usize;
+        (start_word..=end_word)
+            .map(move |word_idx| {
+                let mut word = self.words[word_idx];
+                if word_idx == start_word {
+                    word &= low_mask(start_bit);
+                }
+                if word_idx == end_word && end_word == high_word {
+                    word &= high_mask(end_bit);
+                }
+                (word_idx, word)
+            })
+    }
+
+    /// Walks the summary index first so empty blocks are skipped wholesale, then
+    /// repeatedly pulls the lowest set bit out of each non-empty word.
+    fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.summary
+            .iter()
+            .enumerate()
+            .filter(|(_, &block)| block != 0)
+            .flat_map(move |(block_idx, &block)| {
+                SetBitIter(block)
+                    .map(move |bit_in_block| block_idx * WORDS_PER_BLOCK + bit_in_block as usize)
+            })
+            .flat_map(move |word_idx| {
+                let word = self.words[word_idx];
+                SetBitIter(word).map(move |bit| (word_idx as u32) * 64 + bit)
+            })
+    }
+}
+
+impl Default for Set {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Iterates the set bits of a single word low-to-high by repeatedly extracting
+/// `trailing_zeros` and clearing it.
+struct SetBitIter(u64);
+
+impl Iterator for SetBitIter {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.0 == 0 {
+            return None;
+        }
+        let bit = self.0.trailing_zeros();
+        self.0 &= self.0 - 1;
+        Some(bit)
+    }
+}
+
+/// Mask with bits `[bit..64)` set, for clearing a word's low bits below a range start.
+fn low_mask(bit: usize) -> u64 {
+    if bit == 0 {
+        u64::MAX
+    } else {
+        u64::MAX << bit
+    }
+}
+
+/// Mask with bits `[0..=bit]` set, for clearing a word's high bits above a range end.
+fn high_mask(bit: usize) -> u64 {
+    if bit >= 63 {
+        u64::MAX
+    } else {
+        (1u64 << (bit + 1)) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force_range(members: &[u32], low: u32, high: u32) -> Vec<u32> {
+        members
+            .iter()
+            .copied()
+            .filter(|&v| v >= low && v <= high)
+            .collect()
+    }
+
+    #[test]
+    fn range_matches_brute_force_reference() {
+        let members = [0u32, 10, 63, 64, 65, 100, 127, 128, 200, 5000];
+        let mut set = Set::new();
+        for &v in &members {
+            set.insert(v);
+        }
+        for (low, high) in [
+            (0, 0),
+            (0, 5000),
+            (5, 5000),
+            (64, 64),
+            (65, 127),
+            (128, 199),
+            (201, 4999),
+            (5000, 5000),
+            (5001, 6000),
+        ] {
+            assert_eq!(
+                set.range(low, high),
+                brute_force_range(&members, low, high),
+                "range({low}, {high})"
+            );
+            assert_eq!(
+                set.count_in_range(low, high),
+                brute_force_range(&members, low, high).len()
+            );
+        }
+    }
+
+    #[test]
+    fn range_is_empty_when_low_exceeds_high_or_set_is_empty() {
+        let mut set = Set::new();
+        assert_eq!(set.range(0, 10), Vec::<u32>::new());
+        set.insert(5);
+        assert_eq!(set.range(10, 1), Vec::<u32>::new());
+        assert_eq!(set.count_in_range(10, 1), 0);
+    }
+
+    #[test]
+    fn to_index_ring_round_trips_values_above_24_bit_default() {
+        let mut set = Set::new();
+        set.insert(1);
+        set.insert(20_000_000);
+        let ring = set.to_index_ring();
+        let mut round_tripped = ring.to_array();
+        round_tripped.sort_unstable();
+        assert_eq!(round_tripped, vec![1, 20_000_000]);
+    }
+}